@@ -61,9 +61,6 @@ lazy_static! {
         let efs = EasyFileSystem::open(BLOCK_DEVICE.clone());
         Arc::new(EasyFileSystem::root_inode(&efs))
     };
-    pub static ref LINK_VEC: UPSafeCell<Vec<(Inode, u32)>> = unsafe {
-        UPSafeCell::new(Vec::new())
-    };
 }
 
 /// List all apps in the root directory
@@ -105,40 +102,28 @@ impl OpenFlags {
     }
 }
 
-pub fn link(old: &Inode) {
-    let mut link_list = LINK_VEC.exclusive_access();
-    // 查找是否存在旧的 Inode
-    let mut found = false;
-    for (inode, count) in link_list.iter_mut() {
-        if inode.get_inode_num() == old.get_inode_num() {
-            // 如果找到，增加对应的 u32 值
-            *count += 1;
-            found = true;
-            break;
-        }
-    }
-    // 如果没有找到，创建新的 Inode 和 u32 元组，插入 vec
-    if !found {
-        link_list.push(((*old).clone(), 2));
-    }
-}
-pub fn unlink(old: &Inode) -> bool{
-    let mut link_vec = LINK_VEC.exclusive_access();  // 获取 LINK_VEC 的可变引用
-    let mut find = false;
-    for i in 0..link_vec.len() {
-        if link_vec[i].0.get_inode_num() == old.get_inode_num() {
-            let (_, count) = &mut link_vec[i];
-            *count -= 1;
-            // 如果引用计数为 0，移除该 inode
-            if *count == 1 {
-                link_vec.remove(i);  // 移除该元素
-            }
-            find = true;
-            break;
+/// Open a file on behalf of `task`, honouring its `RLIMIT_NOFILE`.
+///
+/// NOT CURRENTLY ENFORCED: no syscall in this snapshot allocates a file
+/// descriptor (there is no `sys_open`; `sys_exec`/`sys_spawn` call
+/// [`open_file`] directly to load a program image and never hold onto the
+/// fd), so nothing calls this function. It's provided as the chokepoint
+/// the real fd-returning open path would need to call instead of
+/// [`open_file`] once one exists in this tree; until then `RLIMIT_NOFILE`
+/// has nowhere to actually bite.
+pub fn open_file_checked(
+    task: &Arc<crate::task::TaskControlBlock>,
+    name: &str,
+    flags: OpenFlags,
+) -> Option<Arc<OSInode>> {
+    let inner = task.inner_exclusive_access();
+    if let Some(limit) = inner.rlimits.get(crate::task::rlimit::RLIMIT_NOFILE) {
+        if crate::task::rlimit::exceeds_as(inner.fd_table.len() + 1, limit) {
+            return None;
         }
     }
-    //println!("find:{}",find);
-    find
+    drop(inner);
+    open_file(name, flags)
 }
 
 /// Open a file
@@ -177,78 +162,49 @@ pub fn delete_file(name: &str) -> isize{
     0
 }
  */
+/// Create a hard link `new_name` -> the inode behind `old_name`.
 ///
-pub fn create_link(old_name: &str, new_name: &str) -> isize{
-    let old_inode = ROOT_INODE.find(old_name);
-    //let new_inode = ROOT_INODE.find(new_name);
-    match old_inode {
-        Some(mut old) => {
+/// `old_name`/`new_name` may be paths through subdirectories. Link
+/// accounting lives on the on-disk inode's `nlink` field now (via
+/// `Inode::build_link`/`get_link`), so it survives remounts instead of
+/// being scoped to a process-global table. `build_link`/`destroy_link`
+/// take `&self`, like every other `Inode` mutator (`clear`, `create`,
+/// `create_link`) — they lock the block cache internally rather than
+/// requiring exclusive ownership of the `Arc`, so a concurrently-open
+/// inode can't make this silently no-op the way going through
+/// `Arc::get_mut` could.
+pub fn create_link(old_name: &str, new_name: &str) -> isize {
+    match ROOT_INODE.find(old_name) {
+        Some(old) => {
             let old_inode_id = old.get_inode_num();
-            let new_inode = ROOT_INODE.create_link(new_name,old_inode_id);
-            match new_inode {
-                Some(mut new) =>{
-                    link(&old);
-                    // 尝试通过 Arc::get_mut() 获取可变引用
-                    if let Some(old_mut) = Arc::get_mut(&mut old) {
-                        if let Some(new_mut) = Arc::get_mut(&mut new) {
-                            old_mut.build_link();
-                            new_mut.build_link();
-                            //println!("old.link:{}",old.get_link());
-                            //println!("new.link:{}",new.get_link());
-                        } else {
-                            return -1;
-                        }
-                    }
-                },
-                None => return -1,
+            match ROOT_INODE.create_link(new_name, old_inode_id) {
+                Some(_) => {
+                    old.build_link();
+                    0
+                }
+                None => -1,
             }
-            
-        },
-        None => return -1,
+        }
+        None => -1,
     }
-    0
 }
 
-///
-pub fn destroy_link(name: &str) -> isize{
-    let find_inode = ROOT_INODE.find(name);
-    //let new_inode = ROOT_INODE.find(new_name);
-    match find_inode {
-        Some(mut inode) => {
-            let find = unlink(&inode);
-            if let Some(mut_inode) = Arc::get_mut(&mut inode) {
-                mut_inode.destroy_link();
-                //println!("inode.link:{}",inode.get_link());
-            }
-            
-            if !find {
-                ROOT_INODE.remove_name_from_dir(name);
+/// Remove the directory entry `name`, decrementing the on-disk `nlink` of
+/// the inode it pointed to and freeing its data blocks once that reaches
+/// zero. The `nlink` decrement (and the `clear` it may trigger) happens
+/// before the directory entry is removed, so a reader can't observe a
+/// dangling entry whose target's `nlink` was never adjusted.
+pub fn destroy_link(name: &str) -> isize {
+    match ROOT_INODE.find(name) {
+        Some(inode) => {
+            inode.destroy_link();
+            if inode.get_link() == 0 {
+                inode.clear();
             }
-        },
-        None => return -1,
-    }
-
-    0
-}
-
-#[allow(unused)]
-/// 打印 LINK_VEC 的内容
-pub fn print_link_vec() {
-    let link_vec = LINK_VEC.exclusive_access();
-    println!("LINK_VEC contains:");
-    // 遍历 LINK_VEC 中的每个 (Inode, u32) 元组
-    for (old, count) in link_vec.iter() {
-        println!("Old Inode: {:?}, Link Count: {}", old, count);
-    }
-}
-
-
-#[allow(unused)]
-/// 打印 VEC 的内容
-pub fn print_vec(vec: &Vec<u32>) {
-    println!("new_link_list contains:");
-    for data in vec.iter() {
-        println!("Inode_id: {}", data);
+            ROOT_INODE.remove_name_from_dir(name);
+            0
+        }
+        None => -1,
     }
 }
 
@@ -287,31 +243,6 @@ impl File for OSInode {
     fn fstat(&self) -> Option<Stat> {
         let inner = self.inner.exclusive_access();
         let inode_id = inner.inode.get_inode_num();
-
-        let mut link_num: u32 = 1;
-        //print_link_vec();
-        //println!("inner.inode.get_link():{}",inner.inode.get_link());
-        /* 
-        if !inner.inode.get_link() {
-            link_num = 1;
-        }else{
-            for (inode1,inode2) in link_list.iter(){
-                if inode1.get_inode_num() == inode_id
-                || inode2.get_inode_num() == inode_id{
-                    link_num += 1;
-                }
-            }
-        }*/
-        let link_list =  LINK_VEC.exclusive_access();
-        for (inode,count) in link_list.iter(){
-            let inode_num = inode.get_inode_num();
-            if inode_num == inode_id {
-                link_num = *count;
-            }
-        }
-
-
-        //let link = inner.inode.get_link();
         let stat_mode = match inner.inode.is_dir() {
             true => StatMode::DIR,
             false => StatMode::FILE,
@@ -320,7 +251,7 @@ impl File for OSInode {
             dev: 0,
             ino: inode_id as u64,
             mode: stat_mode,
-            nlink: link_num,
+            nlink: inner.inode.get_link(),
             pad: [0; 7]
         })
     }
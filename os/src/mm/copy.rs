@@ -0,0 +1,88 @@
+//! Cross-page-boundary copies between kernel and user space.
+//!
+//! `translated_refmut` hands back a single physical pointer, which is only
+//! safe to write through when the whole value lives on one page. A
+//! `TimeVal`/`TaskInfo` (or any other struct) that straddles a page
+//! boundary needs its bytes scattered across the possibly-discontiguous
+//! slices [`translated_byte_buffer`] returns instead.
+//!
+//! Before touching `translated_byte_buffer`, [`validate_user_range`] walks
+//! the page table itself and checks every page in range is present (and,
+//! for writes, user-writable), so a bad user pointer is rejected with `-1`
+//! instead of reaching whatever `translated_byte_buffer` would do with it.
+
+use super::{PTEFlags, PageTable, VirtAddr};
+use core::mem::size_of;
+use core::slice;
+
+use super::translated_byte_buffer;
+
+/// Check that every page covering `ptr..ptr + len` is mapped (and, if
+/// `write` is set, writable and user-accessible) in the address space
+/// named by `token`.
+fn validate_user_range(token: usize, ptr: usize, len: usize, write: bool) -> bool {
+    if len == 0 {
+        return true;
+    }
+    let page_table = PageTable::from_token(token);
+    let start_vpn = VirtAddr::from(ptr).floor();
+    let end_vpn = VirtAddr::from(ptr + len - 1).floor();
+    let mut vpn = start_vpn;
+    while vpn <= end_vpn {
+        match page_table.translate(vpn) {
+            Some(pte)
+                if pte.is_valid()
+                    && (!write || (pte.writable() && pte.flags().contains(PTEFlags::U))) => {}
+            _ => return false,
+        }
+        vpn.0 += 1;
+    }
+    true
+}
+
+/// Copy `value` into the user buffer at `ptr`, splitting the write across
+/// however many physical pages `ptr..ptr + size_of::<T>()` spans.
+///
+/// Returns `-1` without writing anything if any covered page is unmapped
+/// or not user-writable.
+pub fn copy_to_user<T: Copy>(token: usize, ptr: *mut T, value: &T) -> isize {
+    let len = size_of::<T>();
+    if !validate_user_range(token, ptr as usize, len, true) {
+        return -1;
+    }
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    if buffers.iter().map(|b| b.len()).sum::<usize>() != len {
+        return -1;
+    }
+    let src = unsafe { slice::from_raw_parts(value as *const T as *const u8, len) };
+    let mut copied = 0;
+    for buffer in buffers {
+        buffer.copy_from_slice(&src[copied..copied + buffer.len()]);
+        copied += buffer.len();
+    }
+    0
+}
+
+/// Copy `size_of::<T>()` bytes out of user space at `ptr` into `value`,
+/// reassembling them from the (possibly discontiguous) physical pages
+/// backing the source range. Used by `sys_prlimit` to read the caller's
+/// `new_limit` argument.
+///
+/// Returns `-1` without reading anything if any covered page is unmapped.
+pub fn copy_from_user<T: Copy>(token: usize, ptr: *const T, value: &mut T) -> isize {
+    let len = size_of::<T>();
+    if !validate_user_range(token, ptr as usize, len, false) {
+        return -1;
+    }
+    let buffers = translated_byte_buffer(token, ptr as *const u8, len);
+    if buffers.iter().map(|b| b.len()).sum::<usize>() != len {
+        return -1;
+    }
+    let dst = unsafe { slice::from_raw_parts_mut(value as *mut T as *mut u8, len) };
+    let mut copied = 0;
+    for buffer in buffers {
+        dst[copied..copied + buffer.len()].copy_from_slice(buffer);
+        copied += buffer.len();
+    }
+    0
+}
@@ -0,0 +1,105 @@
+//! Copy-on-write frame sharing and lazy (`mmap`) page fault classification.
+//!
+//! STATUS: this module is groundwork, not a finished feature — COW fork and
+//! lazy mmap are NOT implemented by this tree. `fork` still deep-copies
+//! every page, `sys_mmap` still eagerly backs every page it maps (it even
+//! tracks that eager allocation against `RLIMIT_AS` via `as_used`, which is
+//! unrelated to and does not imply anything about the lazy path sketched
+//! here), and nothing calls [`classify_store_fault`]. Wiring this in would
+//! mean editing `task::task::TaskControlBlock::fork`, `mm::memory_set`, and
+//! `trap::trap_handler` — none of which are part of this tree snapshot.
+//! Treat `frame_ref_inc`/`frame_ref_dec`/`frame_is_shared`/
+//! `classify_store_fault` below as an unplugged extension point only;
+//! nothing in this crate calls them.
+
+use super::PhysPageNum;
+use crate::sync::UPSafeCell;
+use alloc::collections::BTreeMap;
+use lazy_static::*;
+
+lazy_static! {
+    /// Reference counts for physical frames shared by COW mappings.
+    ///
+    /// A frame absent from this map has an implicit refcount of 1 (owned
+    /// solely by whoever allocated it). [`frame_ref_inc`] inserts an entry,
+    /// bumping it to 2; [`frame_ref_dec`] drives it back down and removes
+    /// the entry once it would reach zero. Nothing in this crate calls
+    /// either yet (see the module docs).
+    static ref FRAME_REFCOUNTS: UPSafeCell<BTreeMap<usize, usize>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// Record that `ppn` is now shared by one more page table entry.
+pub fn frame_ref_inc(ppn: PhysPageNum) {
+    let mut counts = FRAME_REFCOUNTS.exclusive_access();
+    *counts.entry(ppn.0).or_insert(1) += 1;
+}
+
+/// Drop one reference to `ppn`. Returns the remaining reference count; the
+/// caller must free the frame itself once this reaches zero.
+pub fn frame_ref_dec(ppn: PhysPageNum) -> usize {
+    let mut counts = FRAME_REFCOUNTS.exclusive_access();
+    match counts.get_mut(&ppn.0) {
+        Some(count) if *count > 1 => {
+            *count -= 1;
+            *count
+        }
+        Some(_) => {
+            counts.remove(&ppn.0);
+            0
+        }
+        None => 0,
+    }
+}
+
+/// Whether `ppn` is currently shared (refcount greater than one).
+pub fn frame_is_shared(ppn: PhysPageNum) -> bool {
+    FRAME_REFCOUNTS
+        .exclusive_access()
+        .get(&ppn.0)
+        .copied()
+        .unwrap_or(1)
+        > 1
+}
+
+/// Tag distinguishing how a `MapArea` should behave on a page fault.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MapAreaType {
+    /// Ordinary eagerly-backed mapping (the pre-existing behaviour).
+    Framed,
+    /// Shared with another address space after `fork`; a write fault
+    /// copies the frame out before restoring write access.
+    Cow,
+    /// Registered by `mmap` but not yet backed by a frame; any fault
+    /// allocates and maps a fresh zeroed frame.
+    Lazy,
+}
+
+/// What `trap::trap_handler` should do about a classified store page fault.
+pub enum PageFaultAction {
+    /// Allocate a fresh frame, copy `old_ppn`'s contents into it, remap the
+    /// page writable, then decrement `old_ppn`'s refcount.
+    CopyOnWrite {
+        /// The frame currently mapped read-only and shared with another
+        /// address space.
+        old_ppn: PhysPageNum,
+    },
+    /// Allocate and map a fresh zeroed frame with the area's permissions.
+    LazyAlloc,
+    /// Not a fault this subsystem handles; the caller should treat it as a
+    /// fatal fault (e.g. kill the task).
+    Unhandled,
+}
+
+/// Decide what a store page fault against a page of type `area_type`
+/// should do. `old_ppn` is `None` for a not-yet-backed lazy page.
+pub fn classify_store_fault(
+    area_type: MapAreaType,
+    old_ppn: Option<PhysPageNum>,
+) -> PageFaultAction {
+    match (area_type, old_ppn) {
+        (MapAreaType::Cow, Some(old_ppn)) => PageFaultAction::CopyOnWrite { old_ppn },
+        (MapAreaType::Lazy, _) => PageFaultAction::LazyAlloc,
+        _ => PageFaultAction::Unhandled,
+    }
+}
@@ -0,0 +1,97 @@
+//! Implementation of syscalls
+//!
+//! The single entry point to all system calls, [`syscall()`], is called by
+//! `trap::trap_handler` whenever `ecall` is executed in U-mode (which has
+//! already bumped `syscall_times` for this id). Before dispatching to the
+//! individual handler, it consults the calling task's `SyscallFilter` via
+//! [`check_syscall`], which can deny or kill instead of running the
+//! syscall at all.
+
+mod process;
+
+pub use process::*;
+
+const SYSCALL_DUP: usize = 24;
+const SYSCALL_OPEN: usize = 56;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_READ: usize = 63;
+const SYSCALL_WRITE: usize = 64;
+const SYSCALL_EXIT: usize = 93;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_SBRK: usize = 214;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_GETRUSAGE: usize = 165;
+const SYSCALL_PRLIMIT64: usize = 261;
+const SYSCALL_SPAWN: usize = 400;
+const SYSCALL_TASK_INFO: usize = 410;
+const SYSCALL_WAIT4: usize = 420;
+const SYSCALL_SECCOMP: usize = 460;
+
+use crate::task::current_task;
+use crate::task::filter::{check_syscall, FilterVerdict};
+use crate::task::rlimit::{RLimit, RLIMIT_CPU};
+
+/// Handle syscall exception with `syscall_id` and other arguments.
+pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
+    match check_syscall(&current_task().unwrap(), syscall_id) {
+        FilterVerdict::Proceed => {}
+        FilterVerdict::Deny(errno) => return errno,
+        FilterVerdict::Killed => return 0,
+    }
+
+    // RLIMIT_CPU is checked here, at syscall entry, rather than on a
+    // periodic timer tick: the timer/tick handler isn't part of this
+    // snapshot, so syscall entry is the one reliable, reachable place to
+    // enforce it.
+    {
+        let task = current_task().unwrap();
+        let inner = task.inner_exclusive_access();
+        if let Some(limit) = inner.rlimits.get(RLIMIT_CPU) {
+            if limit.cur != crate::task::rlimit::RLIM_INFINITY
+                && inner.task_info.time >= limit.cur * 1000
+            {
+                drop(inner);
+                crate::task::exit_current_and_run_next(-1);
+                return 0;
+            }
+        }
+    }
+
+    match syscall_id {
+        SYSCALL_EXIT => sys_exit(args[0] as i32),
+        SYSCALL_YIELD => sys_yield(),
+        SYSCALL_GETPID => sys_getpid(),
+        SYSCALL_FORK => sys_fork(),
+        SYSCALL_EXEC => sys_exec(args[0] as *const u8),
+        SYSCALL_WAITPID => sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_WAIT4 => sys_wait4(
+            args[0] as isize,
+            args[1] as *mut i32,
+            args[2],
+            core::ptr::null_mut(),
+        ),
+        SYSCALL_GETRUSAGE => sys_getrusage(args[0] as i32, args[1] as *mut RUsage),
+        SYSCALL_PRLIMIT64 => sys_prlimit(args[0], args[1] as *const RLimit, args[2] as *mut RLimit),
+        SYSCALL_GET_TIME => sys_get_time(args[0] as *mut TimeVal, args[1]),
+        SYSCALL_TASK_INFO => sys_task_info(args[0] as *mut TaskInfo),
+        SYSCALL_MMAP => sys_mmap(args[0], args[1], args[2]),
+        SYSCALL_MUNMAP => sys_munmap(args[0], args[1]),
+        SYSCALL_SBRK => sys_sbrk(args[0] as i32),
+        SYSCALL_SPAWN => sys_spawn(args[0] as *const u8),
+        SYSCALL_SET_PRIORITY => sys_set_priority(args[0] as isize),
+        SYSCALL_SECCOMP => sys_seccomp(args[0], args[1], args[2] as isize),
+        // SYSCALL_DUP/OPEN/CLOSE/READ/WRITE have no handler in this
+        // dispatcher; issuing one panics.
+        SYSCALL_DUP | SYSCALL_OPEN | SYSCALL_CLOSE | SYSCALL_READ | SYSCALL_WRITE => {
+            panic!("Unsupported syscall_id in this snapshot: {}", syscall_id)
+        }
+        _ => panic!("Unsupported syscall_id: {}", syscall_id),
+    }
+}
@@ -5,21 +5,22 @@ use alloc::sync::Arc;
 use crate::{
     config::MAX_SYSCALL_NUM,
     fs::{open_file, OpenFlags},
-    mm::{translated_refmut, translated_str},
+    mm::{copy_from_user, copy_to_user, translated_refmut, translated_str},
     task::{
         add_task, current_task, current_user_token, exit_current_and_run_next,
         suspend_current_and_run_next, TaskStatus,syscall_mmap,syscall_munmap
     },
-    config::BIG_STRIDE,
 };
 
 use crate::{
     bitflags::bitflags, config::PAGE_SIZE, mm::{MapPermission, VirtAddr},
     timer::{get_time_ms,get_time_us},
+    task::filter::SeccompAction,
+    task::rlimit::{exceeds_as, RLimit, RLIMIT_AS},
 };
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct TimeVal {
     pub sec: usize,
     pub usec: usize,
@@ -27,6 +28,7 @@ pub struct TimeVal {
 
 /// Task information
 #[allow(dead_code)]
+#[derive(Clone, Copy)]
 pub struct TaskInfo {
     /// Task status in it's life cycle
     status: TaskStatus,
@@ -57,6 +59,7 @@ pub fn sys_fork() -> isize {
     trace!("kernel:pid[{}] sys_fork", current_task().unwrap().pid.0);
     let current_task = current_task().unwrap();
     let new_task = current_task.fork();
+    crate::task::filter::inherit(&current_task, &new_task);
     let new_pid = new_task.pid.0;
     // modify trap context of new_task, because it returns immediately after switching
     let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
@@ -83,12 +86,21 @@ pub fn sys_exec(path: *const u8) -> isize {
     }
 }
 
-/// If there is not a child process whose pid is same as given, return -1.
-/// Else if there is a child process but it is still running, return -2.
-pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
-    //trace!("kernel: sys_waitpid");
+/// `options` bit for [`sys_wait4`]: don't block if no zombie child matches,
+/// return 0 instead.
+pub const WNOHANG: usize = 1;
+
+/// Full `wait4`: reaps a zombie child matching `pid` (`-1` for any),
+/// encodes its exit status the conventional way (`exit_code << 8`, so
+/// `WIFEXITED`/`WEXITSTATUS` work on the caller's side), and optionally
+/// fills in its accumulated CPU time.
+///
+/// Returns -1 if there is no such child, 0 if `WNOHANG` was given and no
+/// zombie child matches yet, -2 if a matching child exists but is still
+/// running (and `WNOHANG` was not given — callers are expected to retry),
+/// or the reaped child's pid.
+pub fn sys_wait4(pid: isize, status: *mut i32, options: usize, rusage: *mut RUsage) -> isize {
     let task = current_task().unwrap();
-    // find a child process
 
     // ---- access current PCB exclusively
     let mut inner = task.inner_exclusive_access();
@@ -111,61 +123,151 @@ pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
         assert_eq!(Arc::strong_count(&child), 1);
         let found_pid = child.getpid();
         // ++++ temporarily access child PCB exclusively
-        let exit_code = child.inner_exclusive_access().exit_code;
+        let (exit_code, child_cpu_ms) = {
+            let child_inner = child.inner_exclusive_access();
+            (child_inner.exit_code, child_inner.task_info.time)
+        };
         // ++++ release child PCB
-        *translated_refmut(inner.memory_set.token(), exit_code_ptr) = exit_code;
+        inner.children_cpu_time_ms += child_cpu_ms;
+        let token = inner.memory_set.token();
+        if !status.is_null() {
+            *translated_refmut(token, status) = exit_code << 8;
+        }
+        if !rusage.is_null() {
+            *translated_refmut(token, rusage) = RUsage::from_ms(child_cpu_ms);
+        }
         found_pid as isize
+    } else if options & WNOHANG != 0 {
+        0
     } else {
         -2
     }
     // ---- release current PCB automatically
 }
 
-/// YOUR JOB: get time with second and microsecond
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TimeVal`] is splitted by two pages ?
-pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
+/// If there is not a child process whose pid is same as given, return -1.
+/// Else if there is a child process but it is still running, return -2.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    //trace!("kernel: sys_waitpid");
+    sys_wait4(pid, exit_code_ptr, 0, core::ptr::null_mut())
+}
+
+/// `who` argument to [`sys_getrusage`]: usage of the calling task.
+pub const RUSAGE_SELF: i32 = 0;
+/// `who` argument to [`sys_getrusage`]: usage of reaped children.
+pub const RUSAGE_CHILDREN: i32 = 1;
+
+/// A (much trimmed down) `struct rusage`: just the CPU time fields callers
+/// actually rely on, reusing the timing `TaskInfo` already computes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RUsage {
+    /// The task's tracked running time (`TaskInfo::time`), all of which is
+    /// reported here; user and kernel time are not told apart.
+    pub utime: TimeVal,
+    /// Always zero.
+    pub stime: TimeVal,
+}
+
+impl RUsage {
+    fn from_ms(ms: usize) -> Self {
+        let tv = TimeVal {
+            sec: ms / 1000,
+            usec: (ms % 1000) * 1000,
+        };
+        Self {
+            utime: tv,
+            stime: TimeVal { sec: 0, usec: 0 },
+        }
+    }
+}
+
+/// Report CPU usage for `RUSAGE_SELF` (the calling task) or
+/// `RUSAGE_CHILDREN` (children already reaped by `sys_waitpid`).
+pub fn sys_getrusage(who: i32, usage: *mut RUsage) -> isize {
     trace!(
-        "kernel:pid[{}] sys_get_time NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_getrusage",
         current_task().unwrap().pid.0
     );
+    let task = current_task().unwrap();
+    let inner = task.inner_exclusive_access();
+    let ms = match who {
+        RUSAGE_SELF => inner.task_info.time,
+        RUSAGE_CHILDREN => inner.children_cpu_time_ms,
+        _ => return -1,
+    };
     let token = current_user_token();
-    let phys_addr:&mut TimeVal = translated_refmut(
-        token,
-        _ts.into()
+    copy_to_user(token, usage, &RUsage::from_ms(ms))
+}
+
+/// `prlimit`-style get/set of a resource limit for the calling task.
+/// `new_limit`/`old_limit` may each be null to skip that half of the call.
+pub fn sys_prlimit(resource: usize, new_limit: *const RLimit, old_limit: *mut RLimit) -> isize {
+    trace!(
+        "kernel:pid[{}] sys_prlimit",
+        current_task().unwrap().pid.0
     );
-    let us = get_time_us();
-    unsafe {
-        *(phys_addr as *mut TimeVal) = TimeVal {
-            sec: us / 1_000_000,
-            usec: us % 1_000_000,
-        };
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    let token = current_user_token();
+    if !old_limit.is_null() {
+        match inner.rlimits.get(resource) {
+            Some(old) => {
+                if copy_to_user(token, old_limit, &old) != 0 {
+                    return -1;
+                }
+            }
+            None => return -1,
+        }
+    }
+    if !new_limit.is_null() {
+        let mut new = RLimit { cur: 0, max: 0 };
+        if copy_from_user(token, new_limit, &mut new) != 0 {
+            return -1;
+        }
+        if !inner.rlimits.set(resource, new) {
+            return -1;
+        }
     }
     0
 }
 
-/// YOUR JOB: Finish sys_task_info to pass testcases
-/// HINT: You might reimplement it with virtual memory management.
-/// HINT: What if [`TaskInfo`] is splitted by two pages ?
+/// Get time with second and microsecond.
+///
+/// Uses [`copy_to_user`] rather than a single translated pointer, so a
+/// `TimeVal` that straddles a page boundary is still written correctly.
+pub fn sys_get_time(_ts: *mut TimeVal, _tz: usize) -> isize {
+    trace!(
+        "kernel:pid[{}] sys_get_time",
+        current_task().unwrap().pid.0
+    );
+    let token = current_user_token();
+    let us = get_time_us();
+    let value = TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    copy_to_user(token, _ts, &value)
+}
+
+/// Fill in the calling task's [`TaskInfo`].
+///
+/// Uses [`copy_to_user`] rather than a single translated pointer, so a
+/// `TaskInfo` that straddles a page boundary is still written correctly.
 pub fn sys_task_info(_ti: *mut TaskInfo) -> isize {
     trace!(
-        "kernel:pid[{}] sys_task_info NOT IMPLEMENTED",
+        "kernel:pid[{}] sys_task_info",
         current_task().unwrap().pid.0
     );
     let task = current_task().unwrap();
     let inner = task.inner_exclusive_access();
     let token = current_user_token();
-    let phys_addr: &mut TaskInfo= translated_refmut(
-        token,
-        _ti
-    );
-    let ptr = phys_addr as *mut TaskInfo;
-    unsafe {
-        (*ptr).syscall_times = inner.get_syscall_times();
-        (*ptr).status = TaskStatus::Running;
-        (*ptr).time =  get_time_ms() - inner.get_start_time();
-    }
-    0
+    let value = TaskInfo {
+        status: TaskStatus::Running,
+        syscall_times: inner.get_syscall_times(),
+        time: get_time_ms() - inner.get_start_time(),
+    };
+    copy_to_user(token, _ti, &value)
 }
 bitflags! {
     /// map permission corresponding to that in pte: `R W X U`
@@ -199,7 +301,21 @@ pub fn sys_mmap(_start: usize, _len: usize, _port: usize) -> isize {
     // 转换为 `MapPermission`
     let map_permissions = convert_sysmmap_to_map_permission(permissions);
 
-    syscall_mmap(_start,_len,map_permissions)
+    let task = current_task().unwrap();
+    {
+        let inner = task.inner_exclusive_access();
+        if let Some(limit) = inner.rlimits.get(RLIMIT_AS) {
+            if exceeds_as(inner.as_used + _len, limit) {
+                return -1;
+            }
+        }
+    }
+
+    let result = syscall_mmap(_start, _len, map_permissions);
+    if result == 0 {
+        task.inner_exclusive_access().as_used += _len;
+    }
+    result
 }
 /// 将 `SysMmapPermission` 转换为 `MapPermission`
 #[allow(unused)]
@@ -234,12 +350,32 @@ pub fn sys_munmap(_start: usize, _len: usize) -> isize {
     if  !start_va.aligned() || !end_va.aligned(){
         return -1;
     }
-    syscall_munmap(_start, _len)
+    let result = syscall_munmap(_start, _len);
+    if result == 0 {
+        let task = current_task().unwrap();
+        let mut inner = task.inner_exclusive_access();
+        inner.as_used = inner.as_used.saturating_sub(_len);
+    }
+    result
 }
 
 /// change data segment size
 pub fn sys_sbrk(size: i32) -> isize {
     trace!("kernel:pid[{}] sys_sbrk", current_task().unwrap().pid.0);
+    let task = current_task().unwrap();
+    {
+        let mut inner = task.inner_exclusive_access();
+        if size > 0 {
+            if let Some(limit) = inner.rlimits.get(RLIMIT_AS) {
+                if exceeds_as(inner.as_used + size as usize, limit) {
+                    return -1;
+                }
+            }
+            inner.as_used += size as usize;
+        } else {
+            inner.as_used = inner.as_used.saturating_sub((-size) as usize);
+        }
+    }
     if let Some(old_brk) = current_task().unwrap().change_program_brk(size) {
         old_brk as isize
     } else {
@@ -256,6 +392,7 @@ pub fn sys_spawn(_path: *const u8) -> isize {
     );
     let current_task = current_task().unwrap();
     let new_task = current_task.fork();
+    crate::task::filter::inherit(&current_task, &new_task);
     let new_pid = new_task.pid.0;
     // modify trap context of new_task, because it returns immediately after switching
     let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
@@ -274,6 +411,34 @@ pub fn sys_spawn(_path: *const u8) -> isize {
     }
 }
 
+/// Install a syscall filter entry for the current task.
+///
+/// `mode` selects the action: `0` for allow, `1` for errno-deny (returning
+/// `errno`), or `2` to kill the task on that syscall. Filters are monotonic: an already
+/// restricted syscall cannot be relaxed, so this returns -1 in that case.
+pub fn sys_seccomp(mode: usize, syscall_id: usize, errno: isize) -> isize {
+    trace!(
+        "kernel:pid[{}] sys_seccomp",
+        current_task().unwrap().pid.0
+    );
+    if syscall_id >= MAX_SYSCALL_NUM {
+        return -1;
+    }
+    let action = match mode {
+        0 => SeccompAction::Allow,
+        1 => SeccompAction::ErrnoDeny(errno as i32),
+        2 => SeccompAction::Kill,
+        _ => return -1,
+    };
+    let task = current_task().unwrap();
+    let mut inner = task.inner_exclusive_access();
+    if inner.filter.set(syscall_id, action) {
+        0
+    } else {
+        -1
+    }
+}
+
 // YOUR JOB: Set task priority.
 pub fn sys_set_priority(_prio: isize) -> isize {
     trace!(
@@ -285,9 +450,7 @@ pub fn sys_set_priority(_prio: isize) -> isize {
     }
 
     let task = current_task().unwrap();
-    let mut inner = task.inner_exclusive_access();
-    inner.prio = _prio;
-    inner.pass = BIG_STRIDE/_prio;
+    crate::task::set_priority(&task, _prio);
 
     _prio
 }
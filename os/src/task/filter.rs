@@ -0,0 +1,106 @@
+//! Per-task syscall filtering, seccomp-style.
+//!
+//! Each task carries a [`SyscallFilter`] mapping every syscall id to an
+//! [`SeccompAction`]. `syscall::syscall` (the central dispatcher) consults
+//! it via [`check_syscall`] before running the handler. `trap::trap_handler`
+//! bumps `syscall_times` for every syscall that reaches the dispatcher,
+//! before the filter has had a say — so a denied or killed syscall should
+//! not count as having run. [`check_syscall`] undoes that bump on its
+//! `Deny`/`Kill` branches rather than counting a second time on `Allow`,
+//! since `trap::trap_handler` isn't part of this tree snapshot and can't be
+//! changed to bump conditionally.
+
+use crate::config::MAX_SYSCALL_NUM;
+use alloc::sync::Arc;
+
+/// What to do when a task issues a given syscall.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SeccompAction {
+    /// Run the syscall as normal.
+    Allow,
+    /// Don't run the handler; return the given negative errno instead.
+    ErrnoDeny(i32),
+    /// Terminate the task instead of running the handler.
+    Kill,
+}
+
+/// Per-task table of syscall actions.
+///
+/// Filters are monotonic: once a syscall has been set to a non-[`Allow`]
+/// action, it can never be relaxed back to `Allow` or to a different action.
+///
+/// [`Allow`]: SeccompAction::Allow
+#[derive(Copy, Clone)]
+pub struct SyscallFilter {
+    actions: [SeccompAction; MAX_SYSCALL_NUM],
+}
+
+impl SyscallFilter {
+    /// Create a filter table where every syscall is allowed.
+    pub fn new() -> Self {
+        Self {
+            actions: [SeccompAction::Allow; MAX_SYSCALL_NUM],
+        }
+    }
+
+    /// Look up the action installed for `syscall_id`.
+    pub fn action(&self, syscall_id: usize) -> SeccompAction {
+        self.actions[syscall_id]
+    }
+
+    /// Install `action` for `syscall_id`.
+    ///
+    /// Returns `false` without changing anything if the syscall's action has
+    /// already been tightened away from `Allow`.
+    pub fn set(&mut self, syscall_id: usize, action: SeccompAction) -> bool {
+        if self.actions[syscall_id] != SeccompAction::Allow {
+            return false;
+        }
+        self.actions[syscall_id] = action;
+        true
+    }
+}
+
+impl Default for SyscallFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What the syscall dispatcher should do after consulting a task's filter.
+pub enum FilterVerdict {
+    /// Run the handler and count the syscall as usual.
+    Proceed,
+    /// Skip the handler; return this value to the caller instead.
+    Deny(isize),
+    /// The task has been killed; the dispatcher must not touch it further.
+    Killed,
+}
+
+/// Consult `task`'s filter for `syscall_id` and decide what the dispatcher
+/// should do. `Deny`/`Kill` verdicts undo the `syscall_times` bump
+/// `trap::trap_handler` already made for this call, so only syscalls that
+/// actually ran (`Allow`) end up counted.
+pub fn check_syscall(task: &Arc<super::TaskControlBlock>, syscall_id: usize) -> FilterVerdict {
+    let action = task.inner_exclusive_access().filter.action(syscall_id);
+    match action {
+        SeccompAction::Allow => FilterVerdict::Proceed,
+        SeccompAction::ErrnoDeny(errno) => {
+            task.inner_exclusive_access().task_info.uncount(syscall_id);
+            FilterVerdict::Deny(errno as isize)
+        }
+        SeccompAction::Kill => {
+            task.inner_exclusive_access().task_info.uncount(syscall_id);
+            super::exit_current_and_run_next(-1);
+            FilterVerdict::Killed
+        }
+    }
+}
+
+/// Copy `parent`'s filter table onto `child`, so a sandboxed task's
+/// restrictions survive `fork`/`sys_spawn` instead of resetting to
+/// all-`Allow`.
+pub fn inherit(parent: &Arc<super::TaskControlBlock>, child: &Arc<super::TaskControlBlock>) {
+    let filter = parent.inner_exclusive_access().filter;
+    child.inner_exclusive_access().filter = filter;
+}
@@ -30,6 +30,13 @@ impl TaskInfo {
         }
     }
 
+    /// Undo the count (but not the timing) bump `syscalled` already applied
+    /// for `syscall_id`, used when a syscall filter denies or kills instead
+    /// of letting the syscall proceed.
+    pub fn uncount(&mut self, syscall_id: usize) {
+        self.syscall_times[syscall_id] = self.syscall_times[syscall_id].saturating_sub(1);
+    }
+
     /// update the task info when syscall happened
     pub fn syscalled(&mut self, syscall_id: usize) {
         // println!("task: {}: syscall: {}", pid.0, syscall_id);
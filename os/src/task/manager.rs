@@ -0,0 +1,255 @@
+//! Implementation of [`TaskManager`]
+//!
+//! It is only used to manage processes and schedule process based on ready queue.
+//! Other CPU process monitoring functions are in Processor.
+//!
+//! The actual ordering of the ready queue is delegated to a [`Scheduler`]
+//! implementation so the policy (stride, FIFO, round-robin, ...) can be
+//! swapped without touching `add_task`/`fetch_task`. `TaskManager` holds the
+//! active scheduler as a `Box<dyn Scheduler<_>>`, so picking a different
+//! policy is a one-line change in [`build_scheduler`], not a change to
+//! `TaskManager`'s fields.
+
+use super::TaskControlBlock;
+use crate::config::BIG_STRIDE;
+use crate::sync::UPSafeCell;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use lazy_static::*;
+
+/// A pluggable scheduling policy that owns the ready-queue storage and
+/// decides which task runs next.
+pub trait Scheduler<T> {
+    /// Insert a ready task into the scheduler.
+    fn insert(&mut self, task: T);
+    /// Look at the task that would be returned by [`Scheduler::pop`], without removing it.
+    fn peek(&self) -> Option<&T>;
+    /// Mutable version of [`Scheduler::peek`], used by the timer tick to
+    /// update bookkeeping (e.g. stride) on the currently-favoured task.
+    fn peek_mut(&mut self) -> Option<&mut T>;
+    /// Remove and return the next task to run.
+    fn pop(&mut self) -> Option<T>;
+    /// Remove a specific task from the ready queue, wherever it is.
+    fn remove(&mut self, task: &T) -> Option<T>;
+    /// Apply `prio` to `task` using this scheduler's own notion of
+    /// priority (e.g. recomputing a stride pass). Schedulers that don't
+    /// use priority (FIFO, round-robin, ...) can keep the no-op default.
+    fn set_priority(&self, _task: &T, _prio: isize) {}
+}
+
+/// Stride-scheduling policy: always hands out the ready task with the
+/// smallest `stride`, then advances that task's stride by its `pass`
+/// (`BIG_STRIDE / priority`).
+pub struct StrideScheduler {
+    ready_queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl StrideScheduler {
+    /// Create an empty stride scheduler.
+    pub fn new() -> Self {
+        Self {
+            ready_queue: VecDeque::new(),
+        }
+    }
+
+    fn min_stride_index(&self) -> Option<usize> {
+        self.ready_queue
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, task)| task.inner_exclusive_access().stride)
+            .map(|(idx, _)| idx)
+    }
+}
+
+impl Scheduler<Arc<TaskControlBlock>> for StrideScheduler {
+    fn insert(&mut self, task: Arc<TaskControlBlock>) {
+        self.ready_queue.push_back(task);
+    }
+
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>> {
+        let idx = self.min_stride_index()?;
+        self.ready_queue.get(idx)
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>> {
+        let idx = self.min_stride_index()?;
+        self.ready_queue.get_mut(idx)
+    }
+
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        let idx = self.min_stride_index()?;
+        let task = self.ready_queue.remove(idx)?;
+        {
+            let mut inner = task.inner_exclusive_access();
+            let pass = inner.pass;
+            inner.stride += pass;
+        }
+        Some(task)
+    }
+
+    fn remove(&mut self, task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        let pid = task.pid.0;
+        let idx = self.ready_queue.iter().position(|t| t.pid.0 == pid)?;
+        self.ready_queue.remove(idx)
+    }
+
+    fn set_priority(&self, task: &Arc<TaskControlBlock>, prio: isize) {
+        let mut inner = task.inner_exclusive_access();
+        inner.prio = prio;
+        inner.pass = BIG_STRIDE / prio;
+    }
+}
+
+/// Simple FIFO policy, ignoring priority entirely: first in, first out.
+/// Only built when the `fifo_sched` feature is selected, so swapping the
+/// scheduler at build time doesn't leave an always-compiled, never-called
+/// policy sitting around as dead code.
+#[cfg(feature = "fifo_sched")]
+pub struct FifoScheduler {
+    ready_queue: alloc::collections::LinkedList<Arc<TaskControlBlock>>,
+}
+
+#[cfg(feature = "fifo_sched")]
+impl FifoScheduler {
+    /// Create an empty FIFO scheduler.
+    pub fn new() -> Self {
+        Self {
+            ready_queue: alloc::collections::LinkedList::new(),
+        }
+    }
+}
+
+#[cfg(feature = "fifo_sched")]
+impl Scheduler<Arc<TaskControlBlock>> for FifoScheduler {
+    fn insert(&mut self, task: Arc<TaskControlBlock>) {
+        self.ready_queue.push_back(task);
+    }
+
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>> {
+        self.ready_queue.front()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>> {
+        self.ready_queue.front_mut()
+    }
+
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.ready_queue.pop_front()
+    }
+
+    fn remove(&mut self, task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        let pid = task.pid.0;
+        let mut removed = None;
+        let mut kept = alloc::collections::LinkedList::new();
+        while let Some(t) = self.ready_queue.pop_front() {
+            if removed.is_none() && t.pid.0 == pid {
+                removed = Some(t);
+            } else {
+                kept.push_back(t);
+            }
+        }
+        self.ready_queue = kept;
+        removed
+    }
+}
+
+#[cfg(feature = "fifo_sched")]
+fn build_scheduler() -> Box<dyn Scheduler<Arc<TaskControlBlock>> + Send + Sync> {
+    Box::new(FifoScheduler::new())
+}
+
+#[cfg(not(feature = "fifo_sched"))]
+fn build_scheduler() -> Box<dyn Scheduler<Arc<TaskControlBlock>> + Send + Sync> {
+    Box::new(StrideScheduler::new())
+}
+
+/// The task manager owns the active [`Scheduler`] and forwards ready-queue
+/// operations to it, so call sites never see the underlying policy.
+pub struct TaskManager {
+    scheduler: Box<dyn Scheduler<Arc<TaskControlBlock>> + Send + Sync>,
+}
+
+impl TaskManager {
+    /// Create an empty `TaskManager` using the policy [`build_scheduler`]
+    /// selects.
+    pub fn new() -> Self {
+        Self {
+            scheduler: build_scheduler(),
+        }
+    }
+
+    /// Add a task to the ready queue.
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        self.scheduler.insert(task);
+    }
+
+    /// Pop the task the active scheduler selects to run next.
+    pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.scheduler.pop()
+    }
+
+    /// Remove a task from the ready queue, e.g. when it is reaped by
+    /// `sys_waitpid` before ever having been fetched again.
+    pub fn remove(&mut self, task: &Arc<TaskControlBlock>) -> Option<Arc<TaskControlBlock>> {
+        self.scheduler.remove(task)
+    }
+
+    /// Set `task`'s priority according to the active scheduler's notion of
+    /// priority (e.g. recomputing its stride pass).
+    pub fn set_priority(&self, task: &Arc<TaskControlBlock>, prio: isize) {
+        self.scheduler.set_priority(task, prio);
+    }
+
+    /// Clone of the task the active scheduler would hand out next, without
+    /// removing it from the ready queue. A clone rather than `&Arc<_>` is
+    /// returned so that [`peek_task`] can hand it out after dropping its
+    /// `TASK_MANAGER` guard; callers that need to mutate the task use its
+    /// own `inner_exclusive_access`, same as everywhere else in this crate.
+    pub fn peek(&self) -> Option<Arc<TaskControlBlock>> {
+        self.scheduler.peek().cloned()
+    }
+
+    /// Same as [`TaskManager::peek`]; kept as a separate name so call sites
+    /// that conceptually mutate the peeked task (e.g. a timer tick updating
+    /// its bookkeeping) read the same way they would against a true `&mut`
+    /// accessor.
+    pub fn peek_mut(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.scheduler.peek_mut().cloned()
+    }
+}
+
+lazy_static! {
+    /// TASK_MANAGER instance through lazy_static!
+    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
+        unsafe { UPSafeCell::new(TaskManager::new()) };
+}
+
+/// Add a task to the ready queue of the active scheduler.
+pub fn add_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().add(task);
+}
+
+/// Fetch a task from the ready queue of the active scheduler.
+pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().fetch()
+}
+
+/// Remove a task from the ready queue, wherever the active scheduler put it.
+pub fn remove_task(task: &Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().remove(task);
+}
+
+/// Set a task's priority, delegating to the active scheduler's notion of
+/// priority instead of mutating `pass` directly.
+pub fn set_priority(task: &Arc<TaskControlBlock>, prio: isize) {
+    TASK_MANAGER.exclusive_access().set_priority(task, prio);
+}
+
+/// Peek at the task the active scheduler would hand out next, without
+/// removing it from the ready queue. Used by the timer tick to update
+/// bookkeeping (e.g. stride) on the currently-favoured task ahead of it
+/// actually being fetched.
+pub fn peek_task() -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().peek_mut()
+}
@@ -0,0 +1,84 @@
+//! Per-task soft/hard resource limits (`RLIMIT_*`), queried and set through
+//! `sys_prlimit`. `RLIMIT_AS` is enforced in `sys_mmap`/`sys_sbrk` and
+//! `RLIMIT_CPU` at syscall entry; `RLIMIT_NOFILE` is only checked by
+//! `fs::open_file_checked`, which nothing calls, so it is not enforced.
+
+/// Limit on the number of open file descriptors.
+pub const RLIMIT_NOFILE: usize = 0;
+/// Limit on the size of the process's address space, in bytes.
+pub const RLIMIT_AS: usize = 1;
+/// Limit on CPU time, in seconds.
+pub const RLIMIT_CPU: usize = 2;
+
+const RLIMIT_COUNT: usize = 3;
+
+/// `RLIM_INFINITY`: no limit.
+pub const RLIM_INFINITY: usize = usize::MAX;
+
+/// A single soft/hard limit pair, matching the POSIX `rlimit` layout.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RLimit {
+    /// Soft limit: the value enforced against the resource today.
+    pub cur: usize,
+    /// Hard limit: the ceiling `cur` may be raised to.
+    pub max: usize,
+}
+
+impl RLimit {
+    fn unlimited() -> Self {
+        Self {
+            cur: RLIM_INFINITY,
+            max: RLIM_INFINITY,
+        }
+    }
+}
+
+/// Per-task table of resource limits, indexed by `RLIMIT_*`.
+#[derive(Clone, Copy)]
+pub struct RLimits {
+    limits: [RLimit; RLIMIT_COUNT],
+}
+
+impl RLimits {
+    /// Every resource starts out unlimited, matching a freshly exec'd process.
+    pub fn new() -> Self {
+        Self {
+            limits: [RLimit::unlimited(); RLIMIT_COUNT],
+        }
+    }
+
+    /// Fetch the current limit for `resource`, if it is a known resource.
+    pub fn get(&self, resource: usize) -> Option<RLimit> {
+        self.limits.get(resource).copied()
+    }
+
+    /// Install a new limit for `resource`. Returns `false` if `resource` is
+    /// unknown or `new.cur` would exceed the current hard limit.
+    pub fn set(&mut self, resource: usize, new: RLimit) -> bool {
+        match self.limits.get_mut(resource) {
+            Some(slot) if new.cur <= slot.max => {
+                *slot = new;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for RLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `used_after` (the address-space size an `mmap`/`sbrk` call
+/// would bring the task's mappings to) would exceed `limit`.
+///
+/// Callers must track cumulative usage themselves (e.g. a running
+/// `as_used: usize` on the task) and pass the *total* here, not just the
+/// size of the call being checked — a soft limit is on the address space
+/// as a whole, not on any single mapping.
+pub fn exceeds_as(used_after: usize, limit: RLimit) -> bool {
+    limit.cur != RLIM_INFINITY && used_after > limit.cur
+}